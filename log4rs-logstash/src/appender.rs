@@ -4,17 +4,172 @@ use log::Record;
 use log4rs::append::Append;
 use logstash_rs::Event;
 use logstash_rs::Sender;
+use logstash_rs::UdpSender;
 use logstash_rs::{BufferedTCPSender, TcpSender};
-use std::sync::Arc;
-use std::sync::Mutex;
-use std::time::Duration;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::sync_channel;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Policy applied by [`Appender::append`] when the background worker queue is
+/// already full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Drop the record that is being pushed.
+    DropNewest,
+    /// Evict the oldest queued record to make room for the new one.
+    DropOldest,
+    /// Block the calling thread until the worker drains an entry.
+    Block,
+}
+
+impl Default for Overflow {
+    fn default() -> Overflow {
+        Overflow::Block
+    }
+}
+
+/// Wire transport used to reach the remote Logstash input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    /// Buffered, connection-oriented TCP (the default).
+    Tcp,
+    /// Connectionless, fire-and-forget UDP.
+    Udp,
+}
+
+impl Default for Transport {
+    fn default() -> Transport {
+        Transport::Tcp
+    }
+}
+
+/// Message handed off from the logging hot-path to the background worker.
+enum Message {
+    Event(Box<Event>),
+    /// Drain request: the worker flushes the underlying sender and then
+    /// acknowledges by dropping the held sender half.
+    Flush(std::sync::mpsc::SyncSender<()>),
+}
+
+/// Bounded queue shared between the application threads and the worker.
+///
+/// A plain [`std::sync::mpsc::SyncSender`] cannot evict its oldest entry, so
+/// the queue is expressed directly as a `VecDeque` guarded by a mutex and a
+/// pair of condition variables — the shape a bounded channel would take
+/// internally — which lets us honour every [`Overflow`] policy.
+struct Queue {
+    inner: Mutex<Inner>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+}
+
+struct Inner {
+    deque: VecDeque<Message>,
+    shutdown: bool,
+}
+
+/// Outcome of a worker wait on the queue.
+enum Pop {
+    Message(Message),
+    /// The buffer lifetime elapsed with no new message.
+    Timeout,
+    /// The appender is being dropped.
+    Shutdown,
+}
+
+impl Queue {
+    fn new(capacity: usize) -> Self {
+        Queue {
+            inner: Mutex::new(Inner {
+                deque: VecDeque::new(),
+                shutdown: false,
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Enqueue a record honouring the overflow policy. Returns `false` when the
+    /// record was dropped.
+    fn push(&self, message: Message, overflow: Overflow) -> bool {
+        let mut inner = self.inner.lock().expect("queue mutex poisoned");
+        while inner.deque.len() >= self.capacity {
+            match overflow {
+                Overflow::DropNewest => return false,
+                Overflow::DropOldest => {
+                    inner.deque.pop_front();
+                }
+                Overflow::Block => {
+                    inner = self.not_full.wait(inner).expect("queue mutex poisoned");
+                }
+            }
+        }
+        inner.deque.push_back(message);
+        self.not_empty.notify_one();
+        true
+    }
+
+    /// Block until a message is available, the optional timeout elapses, or the
+    /// queue is shutting down.
+    fn pop(&self, timeout: Option<Duration>) -> Pop {
+        let mut inner = self.inner.lock().expect("queue mutex poisoned");
+        loop {
+            if let Some(message) = inner.deque.pop_front() {
+                self.not_full.notify_one();
+                return Pop::Message(message);
+            }
+            if inner.shutdown {
+                return Pop::Shutdown;
+            }
+            match timeout {
+                None => {
+                    inner = self.not_empty.wait(inner).expect("queue mutex poisoned");
+                }
+                Some(timeout) => {
+                    let (guard, result) = self
+                        .not_empty
+                        .wait_timeout(inner, timeout)
+                        .expect("queue mutex poisoned");
+                    inner = guard;
+                    if result.timed_out() && inner.deque.is_empty() && !inner.shutdown {
+                        return Pop::Timeout;
+                    }
+                }
+            }
+        }
+    }
+
+    fn shutdown(&self) {
+        let mut inner = self.inner.lock().expect("queue mutex poisoned");
+        inner.shutdown = true;
+        self.not_empty.notify_all();
+    }
+}
 
 #[derive(Debug)]
-pub struct Appender<S>
-where
-    S: Sender + Sync + Send + std::fmt::Debug + 'static,
-{
-    sender: Arc<Mutex<S>>,
+pub struct Appender {
+    queue: Arc<Queue>,
+    overflow: Overflow,
+    level: Option<LogLevel>,
+    static_fields: HashMap<String, Value>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl std::fmt::Debug for Queue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Queue")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
 }
 
 #[derive(Debug)]
@@ -26,8 +181,22 @@ pub struct AppenderBuilder {
     buffer_lifetime: Option<Duration>,
     write_timeout: Option<Duration>,
     connection_timeout: Option<Duration>,
+    overflow: Overflow,
+    max_errors_in_row: Option<usize>,
+    reconnect_backoff: Duration,
+    spool_path: Option<PathBuf>,
+    spool_limit: u64,
+    protocol: Transport,
+    static_fields: HashMap<String, Value>,
 }
 
+/// Default upper bound on the on-disk spill file (8 MiB).
+const DEFAULT_SPOOL_LIMIT: u64 = 8 * 1024 * 1024;
+
+/// Event fields populated directly from the [`Record`]; user-supplied kv and
+/// static fields must not overwrite them.
+const RESERVED_FIELDS: [&str; 4] = ["message", "module_path", "file", "line"];
+
 impl Default for AppenderBuilder {
     fn default() -> AppenderBuilder {
         AppenderBuilder {
@@ -38,6 +207,13 @@ impl Default for AppenderBuilder {
             buffer_lifetime: Some(Duration::from_secs(1)),
             write_timeout: Some(Duration::from_secs(10)),
             connection_timeout: Some(Duration::from_secs(10)),
+            overflow: Overflow::default(),
+            max_errors_in_row: None,
+            reconnect_backoff: Duration::from_secs(1),
+            spool_path: None,
+            spool_limit: DEFAULT_SPOOL_LIMIT,
+            protocol: Transport::default(),
+            static_fields: HashMap::new(),
         }
     }
 }
@@ -89,32 +265,406 @@ impl AppenderBuilder {
         self
     }
 
+    /// Sets the policy applied when the background worker queue is saturated.
+    pub fn with_overflow(&mut self, overflow: Overflow) -> &mut AppenderBuilder {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Sets how many consecutive send failures are tolerated before the worker
+    /// reports a single error for the outage. Every event is still attempted
+    /// after that; `None` never reports. Only consulted when no spool path is
+    /// set.
+    pub fn with_max_errors_in_row(&mut self, max: Option<usize>) -> &mut AppenderBuilder {
+        self.max_errors_in_row = max;
+        self
+    }
+
+    /// Sets the base delay used for exponential reconnect backoff.
+    pub fn with_reconnect_backoff(&mut self, backoff: Duration) -> &mut AppenderBuilder {
+        self.reconnect_backoff = backoff;
+        self
+    }
+
+    /// Sets a file used to spool events to disk while the remote server is
+    /// unreachable. Spooled events are replayed on the next successful send.
+    pub fn with_spool_path(&mut self, path: PathBuf) -> &mut AppenderBuilder {
+        self.spool_path = Some(path);
+        self
+    }
+
+    /// Sets the maximum size, in bytes, the on-disk spill file may reach during
+    /// an outage. Events are dropped once the cap is hit so a long outage cannot
+    /// exhaust disk.
+    pub fn with_spool_limit(&mut self, limit: u64) -> &mut AppenderBuilder {
+        self.spool_limit = limit;
+        self
+    }
+
+    /// Selects the wire transport used to reach Logstash.
+    pub fn with_protocol(&mut self, protocol: Transport) -> &mut AppenderBuilder {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Sets static fields (e.g. service name, environment, host) that are
+    /// attached to every emitted event.
+    pub fn with_static_fields(
+        &mut self,
+        fields: HashMap<String, Value>,
+    ) -> &mut AppenderBuilder {
+        self.static_fields = fields;
+        self
+    }
+
     /// Invoke the builder and return a [`Appender`](struct.Appender.html).
-    pub fn build(&self) -> AnyResult<Appender<BufferedTCPSender>> {
+    pub fn build(&self) -> AnyResult<Appender> {
+        let queue = Arc::new(Queue::new(self.buffer_size.unwrap_or(1024)));
+        let worker = match self.protocol {
+            Transport::Tcp => {
+                let tcp = TcpSender::new(self.hostname.clone(), self.port)
+                    .with_write_timeout(self.write_timeout)
+                    .with_connection_timeout(self.connection_timeout);
+                let sender = BufferedTCPSender::new(tcp, self.buffer_size);
+                self.spawn_worker(queue.clone(), sender)?
+            }
+            // UDP is connectionless, so the connect/write timeouts are no-ops
+            // and `buffer_size` only bounds the in-flight queue.
+            Transport::Udp => {
+                let sender = UdpSender::new(self.hostname.clone(), self.port);
+                self.spawn_worker(queue.clone(), sender)?
+            }
+        };
         Ok(Appender {
-            sender: Arc::new(Mutex::new(BufferedTCPSender::new(
-                TcpSender::new(self.hostname.clone(), self.port),
-                self.buffer_size,
-            ))),
+            queue,
+            overflow: self.overflow,
+            level: self.level,
+            static_fields: self.static_fields.clone(),
+            worker: Mutex::new(Some(worker)),
         })
     }
+
+    /// Wrap `sender` in the resilience layer and start the draining thread.
+    fn spawn_worker<S>(&self, queue: Arc<Queue>, sender: S) -> AnyResult<JoinHandle<()>>
+    where
+        S: Sender + Send + 'static,
+    {
+        let mut resilient = ResilientSender {
+            inner: sender,
+            max_errors_in_row: self.max_errors_in_row,
+            backoff: self.reconnect_backoff,
+            spool_path: self.spool_path.clone(),
+            spool_limit: self.spool_limit,
+            failures: 0,
+            reported: false,
+            spool_full_reported: false,
+            spool_io_reported: false,
+            startup_replayed: false,
+            next_retry: None,
+        };
+        let buffer_lifetime = self.buffer_lifetime;
+        let worker = std::thread::Builder::new()
+            .name("logstash-appender".to_string())
+            .spawn(move || run_worker(&queue, &mut resilient, buffer_lifetime))?;
+        Ok(worker)
+    }
 }
 
-impl<S> Appender<S>
+/// Drain the queue on the dedicated worker thread, owning the sender so that
+/// network stalls never reach application threads.
+fn run_worker<S>(queue: &Queue, sender: &mut ResilientSender<S>, buffer_lifetime: Option<Duration>)
 where
-    S: Sender + Sync + Send + std::fmt::Debug + 'static,
+    S: Sender,
 {
-    pub fn builder() -> AppenderBuilder {
-        AppenderBuilder::default()
+    let mut last_flush = Instant::now();
+    // Whether events have been buffered since the last flush. Guards the timed
+    // flush so an idle appender does not poke the sender once per lifetime.
+    let mut pending = false;
+    loop {
+        match queue.pop(buffer_lifetime) {
+            Pop::Message(Message::Event(event)) => {
+                sender.send(&event);
+                pending = true;
+            }
+            Pop::Message(Message::Flush(ack)) => {
+                sender.flush();
+                pending = false;
+                last_flush = Instant::now();
+                drop(ack);
+            }
+            Pop::Timeout => {}
+            Pop::Shutdown => break,
+        }
+        // Force a flush once the buffer has outlived its configured lifetime so
+        // that a low-traffic logger still delivers in near-real-time.
+        if let Some(lifetime) = buffer_lifetime {
+            if pending && last_flush.elapsed() >= lifetime {
+                sender.flush();
+                pending = false;
+                last_flush = Instant::now();
+            }
+        }
     }
+    sender.flush();
 }
 
-impl<S> Append for Appender<S>
+/// Wraps the underlying [`Sender`] with consecutive-failure tracking, an
+/// exponential reconnect backoff and an optional on-disk spill buffer so that a
+/// transient Logstash outage becomes a recoverable gap rather than lost events.
+///
+/// The retry cadence is driven off a `next_retry` deadline rather than the
+/// per-event path: while disconnected, `append`-driven calls only perform a
+/// cheap, non-blocking spool write, and a full replay is attempted at most once
+/// per backoff interval. This keeps the worker from throttling enqueue
+/// throughput during an outage.
+struct ResilientSender<S> {
+    inner: S,
+    max_errors_in_row: Option<usize>,
+    backoff: Duration,
+    spool_path: Option<PathBuf>,
+    spool_limit: u64,
+    failures: usize,
+    reported: bool,
+    spool_full_reported: bool,
+    /// Set once a spool IO error has been logged this outage, so a sustained
+    /// outage reports a single error instead of one line per event.
+    spool_io_reported: bool,
+    /// Set once the spool left by a previous crash-during-outage has been
+    /// drained on the first healthy send.
+    startup_replayed: bool,
+    /// `Some` while disconnected: the earliest instant a reconnect may be tried.
+    next_retry: Option<Instant>,
+}
+
+impl<S> ResilientSender<S>
 where
-    S: Sender + Sync + Send + std::fmt::Debug + 'static,
+    S: Sender,
 {
+    fn send(&mut self, event: &Event) {
+        if self.spool_path.is_some() {
+            self.send_spooled(event);
+        } else {
+            self.send_direct(event);
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Err(err) = self.inner.flush() {
+            eprintln!("Flush: {:?}", err);
+        }
+    }
+
+    /// No spill buffer: send live and, once the failure ceiling is reached, log
+    /// a single error rather than one per dropped event.
+    fn send_direct(&mut self, event: &Event) {
+        match self.inner.send(event) {
+            Ok(()) => self.reset(),
+            Err(_) => {
+                self.failures += 1;
+                if self.breaker_open() && !self.reported {
+                    self.reported = true;
+                    eprintln!(
+                        "Logstash sender failed {} times in a row; dropping events until a send succeeds",
+                        self.failures
+                    );
+                }
+            }
+        }
+    }
+
+    fn send_spooled(&mut self, event: &Event) {
+        // Believed connected: drain any spool left by a previous
+        // crash-during-outage before the first live send, then try the live
+        // send and only fall back to the spool on failure.
+        if self.next_retry.is_none() {
+            self.drain_leftover_spool();
+            match self.inner.send(event) {
+                Ok(()) => self.reset(),
+                Err(_) => {
+                    self.failures = 1;
+                    self.spool_event(event);
+                    self.next_retry = Some(Instant::now() + self.backoff_delay());
+                }
+            }
+            return;
+        }
+
+        // Disconnected: the spool write is cheap and never sleeps, so enqueue
+        // throughput is unaffected. A replay is only attempted once the backoff
+        // deadline passes.
+        self.spool_event(event);
+        if matches!(self.next_retry, Some(at) if Instant::now() >= at) {
+            if self.try_replay() {
+                self.reset();
+            } else {
+                self.failures += 1;
+                self.next_retry = Some(Instant::now() + self.backoff_delay());
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.failures = 0;
+        self.reported = false;
+        self.spool_full_reported = false;
+        self.spool_io_reported = false;
+        self.next_retry = None;
+    }
+
+    /// Drain a spool file left behind by a previous crash-during-outage. Runs
+    /// once, on the first healthy send, so stranded events are replayed on a
+    /// clean startup instead of waiting for the next outage to trigger a replay.
+    fn drain_leftover_spool(&mut self) {
+        if self.startup_replayed {
+            return;
+        }
+        self.startup_replayed = true;
+        let non_empty = self
+            .spool_path
+            .as_ref()
+            .and_then(|path| std::fs::metadata(path).ok())
+            .map(|meta| meta.len() > 0)
+            .unwrap_or(false);
+        if non_empty {
+            self.try_replay();
+        }
+    }
+
+    /// Log a spool IO error at most once per outage, mirroring the circuit
+    /// breaker's single-shot reporting so a sustained outage cannot flood
+    /// stderr with one line per event.
+    fn report_spool_io(&mut self, context: &str, err: impl std::fmt::Debug) {
+        if !self.spool_io_reported {
+            self.spool_io_reported = true;
+            eprintln!("Logstash spool {}: {:?}", context, err);
+        }
+    }
+
+    /// Whether the consecutive-failure count has reached the configured ceiling.
+    fn breaker_open(&self) -> bool {
+        matches!(self.max_errors_in_row, Some(max) if self.failures >= max)
+    }
+
+    /// `backoff * 2^(failures - 1)`, capped so the delay stays bounded.
+    fn backoff_delay(&self) -> Duration {
+        let shift = self.failures.saturating_sub(1).min(6) as u32;
+        self.backoff.saturating_mul(1u32 << shift)
+    }
+
+    /// Append a single serialized record to the spill file. Cheap and
+    /// non-blocking; drops the event once the file reaches its size cap.
+    fn spool_event(&mut self, event: &Event) {
+        let Some(path) = self.spool_path.clone() else {
+            return;
+        };
+        if std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0) >= self.spool_limit {
+            if !self.spool_full_reported {
+                self.spool_full_reported = true;
+                eprintln!(
+                    "Logstash spool file reached its {}-byte cap; dropping events",
+                    self.spool_limit
+                );
+            }
+            return;
+        }
+        let line = match serde_json::to_string(event) {
+            Ok(line) => line,
+            Err(err) => {
+                self.report_spool_io("serialize", err);
+                return;
+            }
+        };
+        match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(mut file) => {
+                if let Err(err) = writeln!(file, "{}", line) {
+                    self.report_spool_io("write", err);
+                }
+            }
+            Err(err) => self.report_spool_io("open", err),
+        }
+    }
+
+    /// Drain the spill buffer oldest-first. Returns `true` once the file is
+    /// fully replayed and removed; on a mid-replay failure the undelivered tail
+    /// is written back and `false` is returned. Called at most once per backoff
+    /// interval, so the rewrite is not on the per-event path.
+    fn try_replay(&mut self) -> bool {
+        let Some(path) = self.spool_path.clone() else {
+            return false;
+        };
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            // Nothing spooled yet — treat as drained.
+            Err(_) => return true,
+        };
+        let lines: Vec<String> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .collect();
+
+        let mut remaining = Vec::new();
+        let mut draining = true;
+        for line in lines {
+            if draining {
+                match serde_json::from_str::<Event>(&line) {
+                    Ok(event) if self.inner.send(&event).is_ok() => continue,
+                    Ok(_) => draining = false,
+                    Err(err) => {
+                        self.report_spool_io("deserialize", err);
+                        continue;
+                    }
+                }
+            }
+            remaining.push(line);
+        }
+
+        if remaining.is_empty() {
+            let _ = std::fs::remove_file(&path);
+            true
+        } else {
+            if let Ok(mut file) = File::create(&path) {
+                for line in remaining {
+                    let _ = writeln!(file, "{}", line);
+                }
+            }
+            false
+        }
+    }
+}
+
+impl Appender {
+    pub fn builder() -> AppenderBuilder {
+        AppenderBuilder::default()
+    }
+}
+
+/// Merge the per-event structured key-values and the configured static fields
+/// into `event`, skipping the reserved fields populated directly from the
+/// record.
+///
+/// Precedence is reserved record fields > per-event key-values > static fields:
+/// static fields are written first so a key-value of the same name — the more
+/// specific, per-event datum — wins on conflict.
+fn merge_fields(event: &mut Event, kv: HashMap<String, Value>, static_fields: &HashMap<String, Value>) {
+    for (key, value) in static_fields {
+        if !RESERVED_FIELDS.contains(&key.as_str()) {
+            event.with_field(key, value.clone());
+        }
+    }
+    for (key, value) in kv {
+        if !RESERVED_FIELDS.contains(&key.as_str()) {
+            event.with_field(&key, value);
+        }
+    }
+}
+
+impl Append for Appender {
     fn append(&self, record: &Record) -> AnyResult<()> {
-        eprintln!("Append: {:?}", record);
+        // Honour the configured threshold: only records at least as severe as
+        // `level` are forwarded.
+        if matches!(self.level, Some(level) if record.level() > level) {
+            return Ok(());
+        }
         let mut event = Event::new_with_time_now();
         if let Some(path) = record.module_path() {
             event.with_field("module_path", path.into());
@@ -126,13 +676,187 @@ where
             event.with_field("line", line.into());
         }
         event.with_field("message", record.args().to_string().into());
-        let mut sender = self
-            .sender
-            .lock()
-            .map_err(|_| anyhow::anyhow!("Mutex lock failed"))?;
-        eprintln!("Send: {:?}", event);
-        sender.send(&event)?;
+        // Capture the structured key-values carried by the record so they reach
+        // Logstash as first-class fields rather than being collapsed into the
+        // message string.
+        let mut kv = HashMap::new();
+        logstash_rs::key_values::capture_into(&mut kv, record.key_values());
+        merge_fields(&mut event, kv, &self.static_fields);
+        self.queue
+            .push(Message::Event(Box::new(event)), self.overflow);
         Ok(())
     }
-    fn flush(&self) {}
+
+    fn flush(&self) {
+        let (ack, done) = sync_channel(0);
+        // A flush sentinel is ordered behind every previously queued event, so
+        // by the time the worker picks it up the outstanding records have been
+        // sent. Waiting for the ack half to close blocks until that happens.
+        if self.queue.push(Message::Flush(ack), Overflow::Block) {
+            let _ = done.recv();
+        }
+    }
+}
+
+impl Drop for Appender {
+    fn drop(&mut self) {
+        self.queue.shutdown();
+        if let Some(worker) = self.worker.lock().ok().and_then(|mut w| w.take()) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(n: i64) -> Box<Event> {
+        let mut event = Event::new_with_time_now();
+        event.with_field("n", n.into());
+        Box::new(event)
+    }
+
+    fn seq(message: &Message) -> Option<i64> {
+        match message {
+            Message::Event(event) => event.fields.get("n").and_then(Value::as_i64),
+            _ => None,
+        }
+    }
+
+    /// Pop every currently queued event, identifying each by its `n` tag.
+    fn drain(queue: &Queue) -> Vec<i64> {
+        let mut out = Vec::new();
+        while let Pop::Message(message) = queue.pop(Some(Duration::from_millis(0))) {
+            if let Some(n) = seq(&message) {
+                out.push(n);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn drop_newest_rejects_when_full() {
+        let queue = Queue::new(2);
+        assert!(queue.push(Message::Event(event(1)), Overflow::DropNewest));
+        assert!(queue.push(Message::Event(event(2)), Overflow::DropNewest));
+        // Full: the newest push is rejected and the queue is left untouched.
+        assert!(!queue.push(Message::Event(event(3)), Overflow::DropNewest));
+        assert_eq!(drain(&queue), vec![1, 2]);
+    }
+
+    #[test]
+    fn drop_oldest_evicts_front() {
+        let queue = Queue::new(2);
+        queue.push(Message::Event(event(1)), Overflow::DropOldest);
+        queue.push(Message::Event(event(2)), Overflow::DropOldest);
+        // Full: the oldest entry is evicted to make room for the newest.
+        assert!(queue.push(Message::Event(event(3)), Overflow::DropOldest));
+        assert_eq!(drain(&queue), vec![2, 3]);
+    }
+
+    #[test]
+    fn kv_wins_over_static_fields() {
+        let mut event = Event::new_with_time_now();
+        let mut kv = HashMap::new();
+        kv.insert("region".to_string(), Value::from("us-east"));
+        let mut static_fields = HashMap::new();
+        static_fields.insert("region".to_string(), Value::from("default"));
+        static_fields.insert("service".to_string(), Value::from("api"));
+        merge_fields(&mut event, kv, &static_fields);
+        // Per-event kv is the more specific datum, so it wins the name clash.
+        assert_eq!(event.fields.get("region"), Some(&Value::from("us-east")));
+        assert_eq!(event.fields.get("service"), Some(&Value::from("api")));
+    }
+
+    #[test]
+    fn reserved_fields_are_never_overwritten() {
+        let mut event = Event::new_with_time_now();
+        event.with_field("message", "original".into());
+        let mut kv = HashMap::new();
+        kv.insert("message".to_string(), Value::from("hijacked"));
+        kv.insert("user_id".to_string(), Value::from(7));
+        let mut static_fields = HashMap::new();
+        static_fields.insert("file".to_string(), Value::from("evil.rs"));
+        merge_fields(&mut event, kv, &static_fields);
+        assert_eq!(event.fields.get("message"), Some(&Value::from("original")));
+        assert!(event.fields.get("file").is_none());
+        assert_eq!(event.fields.get("user_id"), Some(&Value::from(7)));
+    }
+
+    /// Records the `n` tag of every event it is handed, optionally failing once
+    /// a given number have been accepted to model a mid-replay disconnect.
+    #[derive(Default)]
+    struct RecordingSender {
+        sent: Vec<i64>,
+        fail_after: Option<usize>,
+    }
+
+    impl Sender for RecordingSender {
+        fn send(&mut self, event: &Event) -> AnyResult<()> {
+            if matches!(self.fail_after, Some(limit) if self.sent.len() >= limit) {
+                return Err(anyhow::anyhow!("endpoint down"));
+            }
+            self.sent
+                .push(event.fields.get("n").and_then(Value::as_i64).unwrap_or_default());
+            Ok(())
+        }
+
+        fn flush(&mut self) -> AnyResult<()> {
+            Ok(())
+        }
+    }
+
+    fn resilient(sender: RecordingSender, path: PathBuf) -> ResilientSender<RecordingSender> {
+        ResilientSender {
+            inner: sender,
+            max_errors_in_row: None,
+            backoff: Duration::from_millis(1),
+            spool_path: Some(path),
+            spool_limit: DEFAULT_SPOOL_LIMIT,
+            failures: 0,
+            reported: false,
+            spool_full_reported: false,
+            spool_io_reported: false,
+            startup_replayed: false,
+            next_retry: None,
+        }
+    }
+
+    fn spool_with(label: &str, tags: &[i64]) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("logstash-{}-{}.ndjson", label, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut file = File::create(&path).unwrap();
+        for &n in tags {
+            writeln!(file, "{}", serde_json::to_string(&*event(n)).unwrap()).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn replay_drains_spool_oldest_first() {
+        let path = spool_with("replay", &[1, 2, 3]);
+        let mut sender = resilient(RecordingSender::default(), path.clone());
+        assert!(sender.try_replay());
+        assert_eq!(sender.inner.sent, vec![1, 2, 3]);
+        // A fully drained spool file is removed.
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn replay_keeps_undelivered_tail_on_failure() {
+        let path = spool_with("replay-fail", &[1, 2, 3]);
+        let sender = RecordingSender {
+            sent: Vec::new(),
+            fail_after: Some(2),
+        };
+        let mut sender = resilient(sender, path.clone());
+        assert!(!sender.try_replay());
+        assert_eq!(sender.inner.sent, vec![1, 2]);
+        // The undelivered tail is written back for the next attempt.
+        let remaining = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(remaining.lines().count(), 1);
+        let _ = std::fs::remove_file(&path);
+    }
 }
@@ -0,0 +1,158 @@
+use crate::appender::{Appender, AppenderBuilder};
+use anyhow::Result as AnyResult;
+use log::Level as LogLevel;
+use log4rs::append::Append;
+use log4rs::config::{Deserialize, Deserializers};
+use serde::de::{Deserializer, Error as _};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Declarative configuration for a `kind: logstash` appender block, mirroring
+/// the programmatic [`AppenderBuilder`](crate::appender::AppenderBuilder).
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogstashAppenderConfig {
+    hostname: Option<String>,
+    port: Option<u16>,
+    buffer_size: Option<usize>,
+    #[serde(default, deserialize_with = "de_opt_duration")]
+    buffer_lifetime: Option<Duration>,
+    #[serde(default, deserialize_with = "de_opt_duration")]
+    write_timeout: Option<Duration>,
+    #[serde(default, deserialize_with = "de_opt_duration")]
+    connection_timeout: Option<Duration>,
+    #[serde(default, deserialize_with = "de_opt_level")]
+    level: Option<LogLevel>,
+    #[serde(default)]
+    extra_fields: HashMap<String, Value>,
+}
+
+/// `log4rs` deserializer that turns a `kind: logstash` appender block into an
+/// [`Appender`](crate::appender::Appender).
+pub struct LogstashAppenderDeserializer;
+
+impl Deserialize for LogstashAppenderDeserializer {
+    type Trait = dyn Append;
+    type Config = LogstashAppenderConfig;
+
+    fn deserialize(
+        &self,
+        config: LogstashAppenderConfig,
+        _: &Deserializers,
+    ) -> AnyResult<Box<dyn Append>> {
+        let mut builder = Appender::builder();
+        if let Some(hostname) = config.hostname {
+            builder.with_hostname(&hostname);
+        }
+        if let Some(port) = config.port {
+            builder.with_port(port);
+        }
+        // Only override a builder default when the key is actually present in
+        // the YAML. These fields are `Option` with `#[serde(default)]`, so an
+        // omitted key deserializes to `None` — applying that would wipe the
+        // builder's meaningful defaults (1s buffer lifetime, 10s timeouts) and
+        // silently disable the timed flush and socket timeouts.
+        if config.buffer_size.is_some() {
+            builder.with_buffer_size(config.buffer_size);
+        }
+        if config.buffer_lifetime.is_some() {
+            builder.with_buffer_lifetime(config.buffer_lifetime);
+        }
+        if config.write_timeout.is_some() {
+            builder.with_write_timeout(config.write_timeout);
+        }
+        if config.connection_timeout.is_some() {
+            builder.with_connection_timeout(config.connection_timeout);
+        }
+        builder.with_level(config.level);
+        if !config.extra_fields.is_empty() {
+            builder.with_static_fields(config.extra_fields);
+        }
+        Ok(Box::new(builder.build()?))
+    }
+}
+
+/// Register the `logstash` appender kind so `log4rs::init_file` can pick up
+/// `kind: logstash` blocks.
+pub fn register(deserializers: &mut Deserializers) {
+    deserializers.insert("logstash", LogstashAppenderDeserializer);
+}
+
+/// Parse an optional human-readable duration such as `"1s"` or `"500ms"`.
+fn de_opt_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = Option::<String>::deserialize(deserializer)?;
+    match raw {
+        Some(raw) => parse_duration(&raw).map(Some).map_err(D::Error::custom),
+        None => Ok(None),
+    }
+}
+
+/// Parse an optional log level from the usual names (`trace`..`error`).
+fn de_opt_level<'de, D>(deserializer: D) -> Result<Option<LogLevel>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let raw = Option::<String>::deserialize(deserializer)?;
+    match raw {
+        Some(raw) => LogLevel::from_str(&raw).map(Some).map_err(D::Error::custom),
+        None => Ok(None),
+    }
+}
+
+fn parse_duration(raw: &str) -> Result<Duration, String> {
+    let raw = raw.trim();
+    let split = raw
+        .find(|c: char| c.is_ascii_alphabetic())
+        .ok_or_else(|| format!("missing unit in duration {:?}", raw))?;
+    let (value, unit) = raw.split_at(split);
+    let value: u64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid duration value in {:?}", raw))?;
+    match unit.trim() {
+        "ns" => Ok(Duration::from_nanos(value)),
+        "us" => Ok(Duration::from_micros(value)),
+        "ms" => Ok(Duration::from_millis(value)),
+        "s" => Ok(Duration::from_secs(value)),
+        "m" => value
+            .checked_mul(60)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("duration {:?} overflows", raw)),
+        "h" => value
+            .checked_mul(60 * 60)
+            .map(Duration::from_secs)
+            .ok_or_else(|| format!("duration {:?} overflows", raw)),
+        other => Err(format!("unknown duration unit {:?}", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_supported_units() {
+        assert_eq!(parse_duration("1s").unwrap(), Duration::from_secs(1));
+        assert_eq!(parse_duration("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_duration("1m").unwrap(), Duration::from_secs(60));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration(" 250 us ").unwrap(), Duration::from_micros(250));
+    }
+
+    #[test]
+    fn rejects_missing_or_unknown_unit() {
+        assert!(parse_duration("5").is_err());
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("ms").is_err());
+    }
+
+    #[test]
+    fn rejects_overflowing_duration() {
+        assert!(parse_duration("10000000000000000h").is_err());
+    }
+}
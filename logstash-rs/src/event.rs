@@ -1,11 +1,13 @@
 use crate::event::level_serializer::SerializableLevel;
 use crate::event::logstash_date_format::SerializableDateTime;
-use chrono::{DateTime, SecondsFormat, Utc};
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
 use log::Level;
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
 use serde::ser::SerializeMap;
 use serde::Serializer;
 use serde_json::Value;
-use std::{collections::HashMap, time::SystemTime};
+use std::str::FromStr;
+use std::{collections::HashMap, fmt, time::SystemTime};
 
 #[derive(Debug, serde::Deserialize, Copy, Clone)]
 pub enum TimePrecision {
@@ -22,6 +24,19 @@ pub enum TimePrecision {
     Nanos,
 }
 
+/// Output representation of the `@timestamp` field.
+///
+/// `Rfc3339` keeps the textual form governed by [`TimePrecision`]; the numeric
+/// variants emit an integer instead, matching the `epoch_millis`/`epoch_second`
+/// mappings many Elasticsearch index templates expect.
+#[derive(Debug, serde::Deserialize, Copy, Clone)]
+pub enum TimestampFormat {
+    Rfc3339,
+    EpochSeconds,
+    EpochMillis,
+    EpochNanos,
+}
+
 impl From<TimePrecision> for SecondsFormat {
     fn from(val: TimePrecision) -> Self {
         match val {
@@ -42,6 +57,7 @@ pub struct LogStashRecord {
     pub level: Level,
     pub target: String,
     pub time_precision: TimePrecision,
+    pub timestamp_format: TimestampFormat,
     pub fields: HashMap<String, Value>,
 }
 
@@ -54,7 +70,7 @@ impl serde::Serialize for LogStashRecord {
 
         map.serialize_entry(
             "@timestamp",
-            &SerializableDateTime::new(self.timestamp, self.time_precision),
+            &SerializableDateTime::new(self.timestamp, self.time_precision, self.timestamp_format),
         )?;
 
         if let Some(ref module) = self.module {
@@ -78,6 +94,102 @@ impl serde::Serialize for LogStashRecord {
     }
 }
 
+impl<'de> Deserialize<'de> for LogStashRecord {
+    /// Inverse of the custom [`Serialize`] above, so a record spooled as a
+    /// single JSON object can be read back. Unknown keys — including
+    /// everything in [`fields`](LogStashRecord::fields) — round-trip verbatim.
+    /// `time_precision` and `timestamp_format` are not serialized, so they come
+    /// back as their defaults.
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RecordVisitor;
+
+        impl<'de> Visitor<'de> for RecordVisitor {
+            type Value = LogStashRecord;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a logstash record object")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<LogStashRecord, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut record = LogStashRecord::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    let value: Value = map.next_value()?;
+                    match key.as_str() {
+                        "@timestamp" => {
+                            record.timestamp = parse_timestamp(&value).map_err(de::Error::custom)?;
+                        }
+                        "module" => record.module = value.as_str().map(str::to_string),
+                        "file" => record.file = value.as_str().map(str::to_string),
+                        "line" => record.line = value.as_u64().map(|line| line as u32),
+                        "level" => {
+                            if let Some(level) = value.as_str().and_then(|s| Level::from_str(s).ok()) {
+                                record.level = level;
+                            }
+                        }
+                        "target" => {
+                            record.target = value.as_str().unwrap_or_default().to_string();
+                        }
+                        _ => {
+                            record.fields.insert(key, value);
+                        }
+                    }
+                }
+                Ok(record)
+            }
+        }
+
+        deserializer.deserialize_map(RecordVisitor)
+    }
+}
+
+/// Accept either the RFC3339 string or the numeric epoch forms emitted by
+/// [`SerializableDateTime`].
+fn parse_timestamp(value: &Value) -> Result<DateTime<Utc>, String> {
+    if let Some(text) = value.as_str() {
+        DateTime::parse_from_rfc3339(text)
+            .map(|dt| dt.with_timezone(&Utc))
+            .map_err(|err| err.to_string())
+    } else if let Some(epoch) = value.as_i64() {
+        // No format marker is stored alongside a numeric `@timestamp`, so the
+        // epoch unit is inferred from magnitude: a present-day instant has ~10
+        // digits in seconds, ~13 in millis, ~16 in micros and ~19 in nanos.
+        // This lets a record serialized in any of the numeric formats survive a
+        // spool + replay round-trip instead of being misread as epoch-millis.
+        let abs = epoch.unsigned_abs();
+        let decoded = if abs < 1_000_000_000_000 {
+            Utc.timestamp_opt(epoch, 0).single()
+        } else if abs < 1_000_000_000_000_000 {
+            Utc.timestamp_millis_opt(epoch).single()
+        } else if abs < 1_000_000_000_000_000_000 {
+            Utc.timestamp_micros(epoch).single()
+        } else {
+            Some(Utc.timestamp_nanos(epoch))
+        };
+        decoded.ok_or_else(|| format!("invalid epoch timestamp {}", epoch))
+    } else {
+        Err(format!("unexpected @timestamp value {}", value))
+    }
+}
+
+/// Field names [`LogStashRecord`] serializes as dedicated JSON entries (plus
+/// `message`, which lives in `fields`). Captured key-values must not overwrite
+/// them or they would serialize twice.
+const RESERVED_FIELDS: [&str; 7] = [
+    "@timestamp",
+    "message",
+    "module",
+    "file",
+    "line",
+    "level",
+    "target",
+];
+
 impl LogStashRecord {
     /// Initialize record with current time in `timestamp` field
     pub fn new() -> Self {
@@ -98,6 +210,18 @@ impl LogStashRecord {
         event.target = meta.target().into();
         event.time_precision = TimePrecision::Millis;
         event.add_data("message", record.args().to_string().into());
+        // Capture the record's structured key-values, but skip any whose name
+        // collides with a field the struct already serializes: left unguarded a
+        // kv named `message` would clobber the log message and one named
+        // `module`/`file`/`line`/`level`/`target` would emit a duplicate JSON
+        // key. Mirrors the appender's `merge_fields` guard.
+        let mut fields = HashMap::new();
+        key_values::capture_into(&mut fields, record.key_values());
+        for (key, value) in fields {
+            if !RESERVED_FIELDS.contains(&key.as_str()) {
+                event.add_data(&key, value);
+            }
+        }
         event
     }
 
@@ -131,6 +255,11 @@ impl LogStashRecord {
         self.time_precision = teme_precision;
         self
     }
+
+    pub fn with_timestamp_format(mut self, timestamp_format: TimestampFormat) -> Self {
+        self.timestamp_format = timestamp_format;
+        self
+    }
 }
 
 impl Default for LogStashRecord {
@@ -139,6 +268,7 @@ impl Default for LogStashRecord {
             timestamp: Utc::now(),
             level: Level::Warn,
             time_precision: TimePrecision::Millis,
+            timestamp_format: TimestampFormat::Rfc3339,
             module: Default::default(),
             file: Default::default(),
             line: Default::default(),
@@ -149,20 +279,26 @@ impl Default for LogStashRecord {
 }
 
 mod logstash_date_format {
-    use crate::event::TimePrecision;
+    use crate::event::{TimePrecision, TimestampFormat};
     use chrono::{DateTime, Utc};
     use serde::{self, Serializer};
 
     pub(crate) struct SerializableDateTime {
         date_time: DateTime<Utc>,
         time_precision: TimePrecision,
+        timestamp_format: TimestampFormat,
     }
 
     impl SerializableDateTime {
-        pub fn new(date_time: DateTime<Utc>, time_precision: TimePrecision) -> Self {
+        pub fn new(
+            date_time: DateTime<Utc>,
+            time_precision: TimePrecision,
+            timestamp_format: TimestampFormat,
+        ) -> Self {
             Self {
                 date_time,
                 time_precision,
+                timestamp_format,
             }
         }
     }
@@ -172,11 +308,88 @@ mod logstash_date_format {
         where
             S: Serializer,
         {
-            let s = self
-                .date_time
-                .to_rfc3339_opts(self.time_precision.into(), true);
+            match self.timestamp_format {
+                TimestampFormat::Rfc3339 => {
+                    let s = self
+                        .date_time
+                        .to_rfc3339_opts(self.time_precision.into(), true);
+                    serializer.serialize_str(&s)
+                }
+                TimestampFormat::EpochSeconds => {
+                    serializer.serialize_i64(self.date_time.timestamp())
+                }
+                TimestampFormat::EpochMillis => {
+                    serializer.serialize_i64(self.date_time.timestamp_millis())
+                }
+                TimestampFormat::EpochNanos => serializer
+                    .serialize_i64(self.date_time.timestamp_nanos_opt().unwrap_or_default()),
+            }
+        }
+    }
+}
+
+pub mod key_values {
+    use log::kv::{Error, Key, Source, Value, VisitSource};
+    use serde_json::Value as Json;
+    use std::collections::HashMap;
+
+    /// Walk the structured key-values carried by a [`log::Record`] and insert
+    /// each pair into `fields`, mapping scalar kv types to their JSON
+    /// equivalents and falling back to the string form for complex values.
+    pub fn capture_into(fields: &mut HashMap<String, Json>, source: &dyn Source) {
+        let mut visitor = FieldVisitor { fields };
+        // Visiting only fails if a value's own `Debug`/`Display` fails, which we
+        // treat as best-effort — a bad field should not drop the whole record.
+        let _ = source.visit(&mut visitor);
+    }
+
+    struct FieldVisitor<'a> {
+        fields: &'a mut HashMap<String, Json>,
+    }
+
+    impl<'a, 'kvs> VisitSource<'kvs> for FieldVisitor<'a> {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            self.fields.insert(key.to_string(), to_json(&value));
+            Ok(())
+        }
+    }
+
+    fn to_json(value: &Value) -> Json {
+        if let Some(value) = value.to_bool() {
+            Json::Bool(value)
+        } else if let Some(value) = value.to_u64() {
+            Json::from(value)
+        } else if let Some(value) = value.to_i64() {
+            Json::from(value)
+        } else if let Some(value) = value.to_f64() {
+            Json::from(value)
+        } else if let Some(value) = value.to_borrowed_str() {
+            Json::String(value.to_string())
+        } else {
+            Json::String(value.to_string())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use log::kv::Value as Kv;
 
-            serializer.serialize_str(&s)
+        #[test]
+        fn scalar_kv_map_to_their_json_equivalents() {
+            assert_eq!(to_json(&Kv::from(true)), Json::Bool(true));
+            assert_eq!(to_json(&Kv::from(42u64)), Json::from(42u64));
+            assert_eq!(to_json(&Kv::from(-7i64)), Json::from(-7i64));
+            assert_eq!(to_json(&Kv::from(1.5f64)), Json::from(1.5f64));
+            assert_eq!(to_json(&Kv::from("hello")), Json::String("hello".to_string()));
+        }
+
+        #[test]
+        fn capture_into_inserts_each_pair() {
+            let mut fields = HashMap::new();
+            let source: &[(&str, i64)] = &[("user_id", 42)];
+            capture_into(&mut fields, &source);
+            assert_eq!(fields.get("user_id"), Some(&Json::from(42i64)));
         }
     }
 }
@@ -202,3 +415,52 @@ mod level_serializer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_at(ts: DateTime<Utc>, format: TimestampFormat) -> LogStashRecord {
+        let mut record = LogStashRecord::new();
+        record.timestamp = ts;
+        record.target = "test".to_string();
+        record.add_data("message", Value::from("hello"));
+        record.with_timestamp_format(format)
+    }
+
+    #[test]
+    fn timestamp_round_trips_in_every_numeric_format() {
+        // A whole-second instant survives every format exactly, so a record
+        // spooled in one format and replayed is not silently shifted.
+        let ts = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        for format in [
+            TimestampFormat::Rfc3339,
+            TimestampFormat::EpochSeconds,
+            TimestampFormat::EpochMillis,
+            TimestampFormat::EpochNanos,
+        ] {
+            let json = serde_json::to_string(&record_at(ts, format)).unwrap();
+            let back: LogStashRecord = serde_json::from_str(&json).unwrap();
+            assert_eq!(back.timestamp, ts, "format {:?} did not round-trip", format);
+        }
+    }
+
+    #[test]
+    fn epoch_seconds_is_not_misread_as_millis() {
+        let ts = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let json = serde_json::to_string(&record_at(ts, TimestampFormat::EpochSeconds)).unwrap();
+        let back: LogStashRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.timestamp.timestamp(), 1_700_000_000);
+    }
+
+    #[test]
+    fn fields_round_trip_as_unknown_keys() {
+        let ts = Utc.timestamp_opt(1_700_000_000, 0).single().unwrap();
+        let mut record = record_at(ts, TimestampFormat::Rfc3339);
+        record.add_data("user_id", Value::from(42));
+        let json = serde_json::to_string(&record).unwrap();
+        let back: LogStashRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.fields.get("user_id"), Some(&Value::from(42)));
+        assert_eq!(back.fields.get("message"), Some(&Value::from("hello")));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::Event;
+use crate::Sender;
+use anyhow::anyhow;
+use anyhow::Result as AnyResult;
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Connection-oriented [`Sender`] that streams each event as a single
+/// newline-terminated JSON object over TCP, reconnecting lazily on demand.
+///
+/// The optional connection/write timeouts are applied to the underlying socket
+/// ([`TcpStream::connect_timeout`] and [`TcpStream::set_write_timeout`]) so a
+/// slow or half-open Logstash endpoint cannot stall the sender indefinitely.
+#[derive(Debug)]
+pub struct TcpSender {
+    hostname: String,
+    port: u16,
+    write_timeout: Option<Duration>,
+    connection_timeout: Option<Duration>,
+    stream: Option<TcpStream>,
+}
+
+impl TcpSender {
+    pub fn new(hostname: String, port: u16) -> Self {
+        Self {
+            hostname,
+            port,
+            write_timeout: None,
+            connection_timeout: None,
+            stream: None,
+        }
+    }
+
+    /// Sets the write timeout applied to the socket.
+    pub fn with_write_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.write_timeout = timeout;
+        self
+    }
+
+    /// Sets the timeout applied when establishing the connection.
+    pub fn with_connection_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.connection_timeout = timeout;
+        self
+    }
+
+    /// Lazily (re)establish the connection, honouring the configured timeouts.
+    fn connect(&mut self) -> AnyResult<&mut TcpStream> {
+        if self.stream.is_none() {
+            let addr = (self.hostname.as_str(), self.port)
+                .to_socket_addrs()?
+                .next()
+                .ok_or_else(|| anyhow!("could not resolve {}:{}", self.hostname, self.port))?;
+            let stream = match self.connection_timeout {
+                Some(timeout) => TcpStream::connect_timeout(&addr, timeout)?,
+                None => TcpStream::connect(addr)?,
+            };
+            stream.set_write_timeout(self.write_timeout)?;
+            self.stream = Some(stream);
+        }
+        Ok(self.stream.as_mut().unwrap())
+    }
+}
+
+impl Sender for TcpSender {
+    fn send(&mut self, event: &Event) -> AnyResult<()> {
+        let mut buf = serde_json::to_vec(event)?;
+        buf.push(b'\n');
+        let result = self.connect().and_then(|stream| Ok(stream.write_all(&buf)?));
+        if result.is_err() {
+            // Drop the socket so the next send reconnects.
+            self.stream = None;
+        }
+        result
+    }
+
+    fn flush(&mut self) -> AnyResult<()> {
+        if let Some(stream) = self.stream.as_mut() {
+            stream.flush()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use crate::Event;
+use crate::Sender;
+use anyhow::Result as AnyResult;
+use std::net::UdpSocket;
+
+/// Connectionless [`Sender`] that ships each event as a single
+/// newline-terminated UDP datagram to `hostname:port`.
+///
+/// UDP avoids head-of-line blocking and connection management at the cost of
+/// delivery guarantees, which makes it a good fit for high-volume,
+/// fire-and-forget telemetry. One event is emitted per datagram to stay within
+/// a typical MTU.
+#[derive(Debug)]
+pub struct UdpSender {
+    hostname: String,
+    port: u16,
+    socket: Option<UdpSocket>,
+}
+
+impl UdpSender {
+    pub fn new(hostname: String, port: u16) -> Self {
+        Self {
+            hostname,
+            port,
+            socket: None,
+        }
+    }
+
+    /// Lazily bind the local socket on first use.
+    fn socket(&mut self) -> AnyResult<&UdpSocket> {
+        if self.socket.is_none() {
+            self.socket = Some(UdpSocket::bind("0.0.0.0:0")?);
+        }
+        Ok(self.socket.as_ref().unwrap())
+    }
+}
+
+impl Sender for UdpSender {
+    fn send(&mut self, event: &Event) -> AnyResult<()> {
+        let mut buf = serde_json::to_vec(event)?;
+        buf.push(b'\n');
+        let addr = (self.hostname.as_str(), self.port);
+        self.socket()?.send_to(&buf, addr)?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> AnyResult<()> {
+        Ok(())
+    }
+}